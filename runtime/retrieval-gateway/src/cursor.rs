@@ -0,0 +1,100 @@
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Opaque pagination cursor payload: the tenant and query it was issued for,
+/// plus the offset to resume from. Binding the tenant and `query_hash` into
+/// the signed payload stops a cursor from one query or tenant being replayed
+/// against another.
+#[derive(Debug, Serialize, Deserialize)]
+struct CursorPayload {
+    tenant: String,
+    query_hash: String,
+    offset: u32,
+}
+
+fn sign(signing_key: &str, payload_bytes: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(signing_key.as_bytes())
+        .expect("HMAC accepts a key of any length");
+    mac.update(payload_bytes);
+    general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// Encode `offset` into an opaque cursor bound to `tenant` and `query_hash`,
+/// HMAC-tagged with the gateway's cursor signing key so it can't be
+/// tampered with. Deliberately independent of `KeyManager`'s rotating
+/// receipt-signing keys: a cursor handed out mid-query should stay valid
+/// for the lifetime of that pagination session, not just until the next
+/// key rotation prunes the key that signed it.
+pub fn encode_cursor(signing_key: &str, tenant: &str, query_hash: &str, offset: u32) -> String {
+    let payload = CursorPayload {
+        tenant: tenant.to_string(),
+        query_hash: query_hash.to_string(),
+        offset,
+    };
+    let payload_bytes = serde_json::to_vec(&payload).expect("cursor payload serializes");
+    let signature = sign(signing_key, &payload_bytes);
+
+    let token = serde_json::json!({
+        "payload": general_purpose::STANDARD.encode(&payload_bytes),
+        "signature": signature,
+    });
+    general_purpose::STANDARD.encode(serde_json::to_vec(&token).expect("cursor token serializes"))
+}
+
+/// Decode and validate a cursor previously produced by `encode_cursor`,
+/// returning the offset to resume from. Rejects cursors that are malformed,
+/// fail signature verification, or were issued for a different tenant or
+/// query than the one making the request.
+pub fn decode_cursor(
+    signing_key: &str,
+    cursor: &str,
+    tenant: &str,
+    query_hash: &str,
+) -> Result<u32, String> {
+    let token_bytes = general_purpose::STANDARD
+        .decode(cursor)
+        .map_err(|_| "invalid cursor encoding".to_string())?;
+    let token: serde_json::Value =
+        serde_json::from_slice(&token_bytes).map_err(|_| "invalid cursor format".to_string())?;
+
+    let payload_b64 = token["payload"].as_str().ok_or("invalid cursor format")?;
+    let signature = token["signature"].as_str().ok_or("invalid cursor format")?;
+
+    let payload_bytes = general_purpose::STANDARD
+        .decode(payload_b64)
+        .map_err(|_| "invalid cursor encoding".to_string())?;
+
+    let expected_signature = sign(signing_key, &payload_bytes);
+    if !constant_time_eq(signature.as_bytes(), expected_signature.as_bytes()) {
+        return Err("cursor signature invalid".to_string());
+    }
+
+    let payload: CursorPayload = serde_json::from_slice(&payload_bytes)
+        .map_err(|_| "invalid cursor payload".to_string())?;
+
+    if payload.tenant != tenant {
+        return Err("cursor issued for a different tenant".to_string());
+    }
+    if payload.query_hash != query_hash {
+        return Err("cursor issued for a different query".to_string());
+    }
+
+    Ok(payload.offset)
+}
+
+/// Constant-time byte comparison, so signature checks don't leak timing
+/// information about how many leading bytes matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
@@ -1,5 +1,6 @@
 use actix_web::{web, App, HttpServer, HttpResponse, HttpRequest, Error};
 use actix_web::middleware::Logger;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -7,18 +8,53 @@ use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use sha2::{Sha256, Digest};
-use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Verifier};
 use base64::{Engine as _, engine::general_purpose};
 
+use crate::cursor::{decode_cursor, encode_cursor};
+use crate::keys::KeyManager;
+use crate::metrics::Metrics;
+use crate::nonce_cache::NonceCache;
+use crate::rate_limit::{RateLimiter, RateLimiterConfig};
+use crate::receipt_store::{ReceiptStore, SqlxReceiptStore};
+
+/// Maximum number of sub-queries in a batch executed concurrently at once.
+const BATCH_CONCURRENCY: usize = 8;
+
+/// Maximum number of sub-queries accepted in a single `/query/batch`
+/// request. `BATCH_CONCURRENCY` only bounds how many run at once; without
+/// this, a single request could still enqueue an unbounded number of
+/// sub-queries, each doing a DB write and a signature.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Total number of rows the mock data store exposes for any query, used only
+/// to demonstrate cursor pagination terminating over a bounded result set.
+const MOCK_TOTAL_ROWS: u32 = 25;
+
+/// Default page size when a query doesn't specify `limit`.
+const DEFAULT_PAGE_SIZE: u32 = 10;
+
+/// How often the background task sweeps expired entries out of the nonce
+/// replay cache. moka otherwise only evicts lazily on access.
+const NONCE_EVICTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// Configuration for the Retrieval Gateway
 #[derive(Clone, Debug)]
 pub struct GatewayConfig {
     pub host: String,
     pub port: u16,
-    pub signing_key: Keypair,
     pub tenant_shards: HashMap<String, String>,
     pub max_query_size: usize,
     pub query_timeout_ms: u64,
+    pub database_url: String,
+    pub rate_limits: RateLimiterConfig,
+    /// Shared secret required in the `X-Admin-Token` header for
+    /// admin-only, state-mutating endpoints such as `/keys/rotate`.
+    pub admin_token: String,
+    /// Stable secret HMAC-tagging pagination cursors. Deliberately separate
+    /// from `KeyManager`'s receipt-signing keys, which rotate and prune on
+    /// `MAX_RECEIPT_TTL` -- a cursor handed out mid-pagination shouldn't be
+    /// invalidated by an unrelated key rotation.
+    pub cursor_signing_key: String,
 }
 
 /// Access Receipt for data retrieval operations
@@ -33,6 +69,9 @@ pub struct AccessReceipt {
     pub nonce: String,
     pub expires_at: DateTime<Utc>,
     pub signature: String,
+    /// Id of the signing key used for `signature`, so receipts signed
+    /// before a key rotation still verify against the retired key.
+    pub kid: String,
 }
 
 /// Query request from clients
@@ -43,6 +82,9 @@ pub struct QueryRequest {
     pub filters: Option<HashMap<String, Value>>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    /// Opaque pagination cursor from a previous response's `next_cursor`.
+    /// Supersedes `offset` when present.
+    pub cursor: Option<String>,
 }
 
 /// Query response with receipt
@@ -62,6 +104,9 @@ pub struct QueryMetadata {
     pub result_count: usize,
     pub shard: String,
     pub timestamp: DateTime<Utc>,
+    /// Opaque cursor to fetch the next page, or `None` if this was the last
+    /// page of the result set.
+    pub next_cursor: Option<String>,
 }
 
 /// Error response
@@ -72,10 +117,38 @@ pub struct ErrorResponse {
     pub details: Option<Value>,
 }
 
+/// Request body for `/query/batch`
+#[derive(Debug, Deserialize)]
+pub struct BatchQueryRequest {
+    pub queries: Vec<QueryRequest>,
+}
+
+/// Per-query outcome within a batch response
+#[derive(Debug, Serialize)]
+pub struct BatchQueryResult {
+    pub success: bool,
+    pub data: Option<Vec<Value>>,
+    pub receipt: AccessReceipt,
+    pub error: Option<String>,
+    pub metadata: QueryMetadata,
+}
+
+/// Response body for `/query/batch`
+#[derive(Debug, Serialize)]
+pub struct BatchQueryResponse {
+    pub batch_id: String,
+    pub results: Vec<BatchQueryResult>,
+    pub execution_time_ms: u64,
+}
+
 /// Retrieval Gateway implementation
 pub struct RetrievalGateway {
     config: GatewayConfig,
-    nonce_cache: Arc<Mutex<HashMap<String, DateTime<Utc>>>,
+    key_manager: KeyManager,
+    receipt_store: Arc<dyn ReceiptStore>,
+    rate_limiter: Arc<RateLimiter>,
+    metrics: Arc<Metrics>,
+    nonce_cache: Arc<NonceCache>,
     query_log: Arc<Mutex<Vec<QueryLogEntry>>>,
 }
 
@@ -92,19 +165,47 @@ struct QueryLogEntry {
 }
 
 impl RetrievalGateway {
-    /// Create a new Retrieval Gateway instance
-    pub fn new(config: GatewayConfig) -> Self {
-        Self {
+    /// Create a new Retrieval Gateway instance, connecting its receipt store
+    /// to `config.database_url`.
+    pub async fn new(config: GatewayConfig) -> std::io::Result<Self> {
+        let receipt_store = SqlxReceiptStore::connect(&config.database_url)
+            .await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+
+        let rate_limiter = Arc::new(RateLimiter::new(config.rate_limits.clone()));
+
+        Ok(Self {
             config,
-            nonce_cache: Arc::new(Mutex::new(HashMap::new())),
+            key_manager: KeyManager::new(),
+            receipt_store: Arc::new(receipt_store),
+            rate_limiter,
+            metrics: Arc::new(Metrics::new()),
+            nonce_cache: Arc::new(NonceCache::new()),
             query_log: Arc::new(Mutex::new(Vec::new())),
-        }
+        })
     }
 
     /// Start the HTTP server
     pub async fn start(self) -> std::io::Result<()> {
         let gateway = web::Data::new(self);
-        
+
+        let reconciliation_limiter = gateway.rate_limiter.clone();
+        tokio::spawn(async move {
+            reconciliation_limiter.run_reconciliation_loop().await;
+        });
+
+        let eviction_nonce_cache = gateway.nonce_cache.clone();
+        let eviction_metrics = gateway.metrics.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(NONCE_EVICTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                eviction_nonce_cache.run_pending_tasks();
+                eviction_metrics
+                    .set_nonce_cache_size(eviction_nonce_cache.entry_count() as usize);
+            }
+        });
+
         HttpServer::new(move || {
             App::new()
                 .app_data(gateway.clone())
@@ -112,9 +213,13 @@ impl RetrievalGateway {
                 .service(
                     web::scope("/api/v1")
                         .route("/query", web::post().to(Self::handle_query))
+                        .route("/query/batch", web::post().to(Self::handle_batch_query))
                         .route("/receipt/{id}/verify", web::get().to(Self::verify_receipt))
                         .route("/health", web::get().to(Self::health_check))
                         .route("/stats", web::get().to(Self::get_stats))
+                        .route("/metrics", web::get().to(Self::get_metrics))
+                        .route("/keys", web::get().to(Self::get_keys))
+                        .route("/keys/rotate", web::post().to(Self::rotate_key))
                 )
         })
         .bind(format!("{}:{}", self.config.host, self.config.port))?
@@ -129,7 +234,18 @@ impl RetrievalGateway {
         gateway: web::Data<Self>,
     ) -> Result<HttpResponse, Error> {
         let start_time = std::time::Instant::now();
-        
+
+        // Enforce per-tenant rate limits before doing any work
+        if let Err(limit) = gateway.rate_limiter.check(&payload.tenant) {
+            return Ok(HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", limit.retry_after_secs.to_string()))
+                .json(ErrorResponse {
+                    error: "Rate limit exceeded".to_string(),
+                    code: "RATE_LIMITED".to_string(),
+                    details: None,
+                }));
+        }
+
         // Validate request
         if let Err(e) = gateway.validate_query_request(&payload) {
             return Ok(HttpResponse::BadRequest().json(ErrorResponse {
@@ -154,27 +270,27 @@ impl RetrievalGateway {
         let execution_time = start_time.elapsed().as_millis() as u64;
         
         match query_result {
-            Ok(data) => {
+            Ok((data, next_cursor)) => {
                 // Generate access receipt
                 let receipt = gateway.generate_access_receipt(&payload, &data, execution_time);
-                
+
+                // Persist the receipt so it can be verified later, including
+                // after a restart
+                if let Err(e) = gateway.receipt_store.put(&receipt).await {
+                    log::error!("failed to persist receipt {}: {}", receipt.id, e);
+                }
+
                 // Log query
                 gateway.log_query(&payload, &receipt, true, None, execution_time);
-                
+
                 // Return response with receipt
                 let response = QueryResponse {
                     success: true,
+                    metadata: gateway.query_metadata(&payload, data.len(), execution_time, next_cursor),
                     data: Some(data),
                     receipt,
-                    metadata: QueryMetadata {
-                        query_id: Uuid::new_v4().to_string(),
-                        execution_time_ms: execution_time,
-                        result_count: data.len(),
-                        shard: gateway.get_shard_for_tenant(&payload.tenant),
-                        timestamp: Utc::now(),
-                    },
                 };
-                
+
                 Ok(HttpResponse::Ok().json(response))
             }
             Err(e) => {
@@ -191,39 +307,170 @@ impl RetrievalGateway {
         }
     }
 
+    /// Handle a batch of query requests, executing each sub-query the same
+    /// way `handle_query` would but collecting per-item outcomes instead of
+    /// short-circuiting the whole batch on the first failure.
+    async fn handle_batch_query(
+        req: HttpRequest,
+        payload: web::Json<BatchQueryRequest>,
+        gateway: web::Data<Self>,
+    ) -> Result<HttpResponse, Error> {
+        if payload.queries.len() > MAX_BATCH_SIZE {
+            return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+                error: format!(
+                    "Batch of {} queries exceeds the maximum of {}",
+                    payload.queries.len(),
+                    MAX_BATCH_SIZE
+                ),
+                code: "BATCH_TOO_LARGE".to_string(),
+                details: None,
+            }));
+        }
+
+        let start_time = std::time::Instant::now();
+        let batch_id = Uuid::new_v4().to_string();
+
+        let results: Vec<BatchQueryResult> = stream::iter(payload.queries.iter())
+            .map(|query| gateway.execute_batch_item(&req, query))
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(HttpResponse::Ok().json(BatchQueryResponse {
+            batch_id,
+            results,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+        }))
+    }
+
+    /// Validate, authorize, execute, and receipt a single sub-query of a
+    /// batch request. Unlike `handle_query`, failures are returned as a
+    /// `BatchQueryResult` rather than an HTTP error, so one bad sub-query
+    /// doesn't abort the rest of the batch.
+    async fn execute_batch_item(&self, req: &HttpRequest, payload: &QueryRequest) -> BatchQueryResult {
+        let start_time = std::time::Instant::now();
+
+        if let Err(limit) = self.rate_limiter.check(&payload.tenant) {
+            let execution_time = start_time.elapsed().as_millis() as u64;
+            let error = "Rate limit exceeded".to_string();
+            let receipt = self.generate_error_receipt(payload, &error);
+            self.log_query(payload, &receipt, false, Some(&error), execution_time);
+
+            return BatchQueryResult {
+                success: false,
+                data: None,
+                metadata: self.query_metadata(payload, 0, execution_time, None),
+                receipt,
+                error: Some(format!("{} (retry after {}s)", error, limit.retry_after_secs)),
+            };
+        }
+
+        if let Err(e) = self
+            .validate_query_request(payload)
+            .and_then(|_| self.check_tenant_isolation(req, &payload.tenant))
+        {
+            let execution_time = start_time.elapsed().as_millis() as u64;
+            let receipt = self.generate_error_receipt(payload, &e);
+            self.log_query(payload, &receipt, false, Some(&e), execution_time);
+
+            return BatchQueryResult {
+                success: false,
+                data: None,
+                metadata: self.query_metadata(payload, 0, execution_time, None),
+                receipt,
+                error: Some(e),
+            };
+        }
+
+        match self.execute_query(payload).await {
+            Ok((data, next_cursor)) => {
+                let execution_time = start_time.elapsed().as_millis() as u64;
+                let receipt = self.generate_access_receipt(payload, &data, execution_time);
+
+                if let Err(e) = self.receipt_store.put(&receipt).await {
+                    log::error!("failed to persist receipt {}: {}", receipt.id, e);
+                }
+                self.log_query(payload, &receipt, true, None, execution_time);
+
+                BatchQueryResult {
+                    success: true,
+                    metadata: self.query_metadata(payload, data.len(), execution_time, next_cursor),
+                    data: Some(data),
+                    receipt,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                let execution_time = start_time.elapsed().as_millis() as u64;
+                let receipt = self.generate_error_receipt(payload, &e);
+                self.log_query(payload, &receipt, false, Some(&e), execution_time);
+
+                BatchQueryResult {
+                    success: false,
+                    data: None,
+                    metadata: self.query_metadata(payload, 0, execution_time, None),
+                    receipt,
+                    error: Some(e),
+                }
+            }
+        }
+    }
+
+    /// Build the metadata block shared by single and batch query responses
+    fn query_metadata(
+        &self,
+        req: &QueryRequest,
+        result_count: usize,
+        execution_time_ms: u64,
+        next_cursor: Option<String>,
+    ) -> QueryMetadata {
+        QueryMetadata {
+            query_id: Uuid::new_v4().to_string(),
+            execution_time_ms,
+            result_count,
+            shard: self.get_shard_for_tenant(&req.tenant),
+            timestamp: Utc::now(),
+            next_cursor,
+        }
+    }
+
     /// Verify an access receipt
     async fn verify_receipt(
         path: web::Path<String>,
         gateway: web::Data<Self>,
     ) -> Result<HttpResponse, Error> {
         let receipt_id = path.into_inner();
-        
-        match gateway.verify_receipt_signature(&receipt_id) {
+
+        match gateway.verify_receipt_signature(&receipt_id).await {
             Ok(receipt) => {
                 // Check if receipt is expired
                 if receipt.expires_at < Utc::now() {
+                    gateway.metrics.record_receipt_verification("expired");
                     return Ok(HttpResponse::Gone().json(ErrorResponse {
                         error: "Receipt has expired".to_string(),
                         code: "RECEIPT_EXPIRED".to_string(),
                         details: None,
                     }));
                 }
-                
+
                 // Check if nonce has been used (replay protection)
-                if gateway.is_nonce_used(&receipt.nonce) {
+                if gateway.is_nonce_used(&receipt.nonce).await {
+                    gateway.metrics.record_receipt_verification("replay");
                     return Ok(HttpResponse::Conflict().json(ErrorResponse {
                         error: "Receipt nonce already used".to_string(),
                         code: "RECEIPT_REPLAY".to_string(),
                         details: None,
                     }));
                 }
-                
+
                 // Mark nonce as used
-                gateway.mark_nonce_used(&receipt.nonce);
-                
+                gateway.mark_nonce_used(&receipt.nonce, receipt.expires_at).await;
+
+                gateway.metrics.record_receipt_verification("success");
                 Ok(HttpResponse::Ok().json(receipt))
             }
             Err(e) => {
+                gateway.metrics.record_receipt_verification("invalid_signature");
                 Ok(HttpResponse::Unauthorized().json(ErrorResponse {
                     error: e,
                     code: "INVALID_SIGNATURE".to_string(),
@@ -248,6 +495,59 @@ impl RetrievalGateway {
         HttpResponse::Ok().json(stats)
     }
 
+    /// Prometheus text-format scrape endpoint. Backed by atomic metric
+    /// families updated inline as queries and receipt verifications happen,
+    /// so scraping is O(1) rather than scanning the query log like `/stats`.
+    async fn get_metrics(gateway: web::Data<Self>) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(gateway.metrics.encode())
+    }
+
+    /// JWKS-style listing of public keys usable to verify an `AccessReceipt`
+    async fn get_keys(gateway: web::Data<Self>) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({
+            "keys": gateway.key_manager.public_keys(),
+        }))
+    }
+
+    /// Rotate the active signing key. The previous key is retained,
+    /// retired-but-verifying, until its receipts' max TTL elapses.
+    ///
+    /// Admin-only: requires `X-Admin-Token` to match `config.admin_token`,
+    /// since this mutates shared gateway state.
+    async fn rotate_key(req: HttpRequest, gateway: web::Data<Self>) -> HttpResponse {
+        if let Err(e) = gateway.check_admin_auth(&req) {
+            return HttpResponse::Unauthorized().json(ErrorResponse {
+                error: e,
+                code: "ADMIN_AUTH_REQUIRED".to_string(),
+                details: None,
+            });
+        }
+
+        let new_kid = gateway.key_manager.rotate();
+        HttpResponse::Ok().json(serde_json::json!({
+            "active_kid": new_kid,
+            "keys": gateway.key_manager.public_keys(),
+        }))
+    }
+
+    /// Check the `X-Admin-Token` header against the configured admin
+    /// secret, for endpoints that mutate shared gateway state.
+    fn check_admin_auth(&self, req: &HttpRequest) -> Result<(), String> {
+        let provided = req
+            .headers()
+            .get("X-Admin-Token")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("");
+
+        if provided.is_empty() || provided != self.config.admin_token {
+            return Err("Missing or invalid admin token".to_string());
+        }
+
+        Ok(())
+    }
+
     /// Validate query request
     fn validate_query_request(&self, req: &QueryRequest) -> Result<(), String> {
         if req.tenant.is_empty() {
@@ -289,8 +589,10 @@ impl RetrievalGateway {
         Ok(())
     }
 
-    /// Execute a query against the data store
-    async fn execute_query(&self, req: &QueryRequest) -> Result<Vec<Value>, String> {
+    /// Execute a query against the data store, resuming from `req.cursor`
+    /// (falling back to `req.offset`) and returning a fresh cursor for the
+    /// next page when more rows remain.
+    async fn execute_query(&self, req: &QueryRequest) -> Result<(Vec<Value>, Option<String>), String> {
         // This is a simplified implementation
         // In a real system, you would:
         // 1. Parse and validate the query
@@ -298,24 +600,44 @@ impl RetrievalGateway {
         // 3. Execute against the appropriate data store
         // 4. Apply filters and pagination
         // 5. Return results
-        
+
+        let query_hash = self.hash_query(&req.query);
+        let offset = match &req.cursor {
+            Some(cursor) => decode_cursor(&self.config.cursor_signing_key, cursor, &req.tenant, &query_hash)?,
+            None => req.offset.unwrap_or(0),
+        };
+
         // Simulate query execution
         tokio::time::sleep(tokio::time::Duration::from_millis(
             self.config.query_timeout_ms
         )).await;
-        
-        // Return mock data for now
-        Ok(vec![
-            serde_json::json!({
-                "id": "doc_1",
-                "tenant": req.tenant,
-                "content": "Sample document content",
-                "labels": {
-                    "pii": "masked",
-                    "sensitivity": "medium"
-                }
+
+        // Return a page of mock data for now
+        let page_size = req.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MOCK_TOTAL_ROWS);
+        let page_len = MOCK_TOTAL_ROWS.saturating_sub(offset).min(page_size);
+
+        let data: Vec<Value> = (0..page_len)
+            .map(|i| {
+                serde_json::json!({
+                    "id": format!("doc_{}", offset + i + 1),
+                    "tenant": req.tenant,
+                    "content": "Sample document content",
+                    "labels": {
+                        "pii": "masked",
+                        "sensitivity": "medium"
+                    }
+                })
             })
-        ])
+            .collect();
+
+        let next_offset = offset + page_len;
+        let next_cursor = if next_offset < MOCK_TOTAL_ROWS {
+            Some(encode_cursor(&self.config.cursor_signing_key, &req.tenant, &query_hash, next_offset))
+        } else {
+            None
+        };
+
+        Ok((data, next_cursor))
     }
 
     /// Generate an access receipt for a successful query
@@ -340,12 +662,14 @@ impl RetrievalGateway {
             nonce,
             expires_at,
             signature: String::new(), // Will be set below
+            kid: String::new(),
         };
-        
+
         // Sign the receipt
-        let signature = self.sign_receipt(&receipt);
+        let (kid, signature) = self.sign_receipt(&receipt);
         AccessReceipt {
             signature,
+            kid,
             ..receipt
         }
     }
@@ -366,19 +690,28 @@ impl RetrievalGateway {
             nonce,
             expires_at,
             signature: String::new(),
+            kid: String::new(),
         };
-        
+
         // Sign the receipt
-        let signature = self.sign_receipt(&receipt);
+        let (kid, signature) = self.sign_receipt(&receipt);
         AccessReceipt {
             signature,
+            kid,
             ..receipt
         }
     }
 
-    /// Sign a receipt with the gateway's private key
-    fn sign_receipt(&self, receipt: &AccessReceipt) -> String {
-        let receipt_data = format!(
+    /// Sign a receipt with the gateway's current active signing key,
+    /// returning the key's `kid` alongside the base64-encoded signature.
+    fn sign_receipt(&self, receipt: &AccessReceipt) -> (String, String) {
+        let receipt_data = self.receipt_signing_data(receipt);
+        self.key_manager.sign(receipt_data.as_bytes())
+    }
+
+    /// Reconstruct the exact string that was signed for `receipt`
+    fn receipt_signing_data(&self, receipt: &AccessReceipt) -> String {
+        format!(
             "{}:{}:{}:{}:{}:{}:{}",
             receipt.id,
             receipt.tenant,
@@ -387,21 +720,22 @@ impl RetrievalGateway {
             receipt.query_hash,
             receipt.result_hash,
             receipt.nonce
-        );
-        
-        let signature = self.config.signing_key.sign(receipt_data.as_bytes());
-        general_purpose::STANDARD.encode(signature.to_bytes())
+        )
     }
 
     /// Verify a receipt signature
-    fn verify_receipt_signature(&self, receipt_id: &str) -> Result<AccessReceipt, String> {
-        // In a real implementation, you would:
-        // 1. Retrieve the receipt from storage
-        // 2. Verify the signature
-        // 3. Return the receipt if valid
-        
-        // For now, return an error
-        Err("Receipt verification not implemented".to_string())
+    async fn verify_receipt_signature(&self, receipt_id: &str) -> Result<AccessReceipt, String> {
+        let receipt = self
+            .receipt_store
+            .get(receipt_id)
+            .await?
+            .ok_or_else(|| "Receipt not found".to_string())?;
+
+        let receipt_data = self.receipt_signing_data(&receipt);
+        self.key_manager
+            .verify(&receipt.kid, receipt_data.as_bytes(), &receipt.signature)?;
+
+        Ok(receipt)
     }
 
     /// Hash a query string
@@ -436,19 +770,34 @@ impl RetrievalGateway {
             .to_string()
     }
 
-    /// Check if a nonce has been used
-    fn is_nonce_used(&self, nonce: &str) -> bool {
-        let cache = self.nonce_cache.lock().unwrap();
-        cache.contains_key(nonce)
+    /// Check if a nonce has been used. Consults the in-process cache first
+    /// and falls back to the durable receipt store, so replay protection
+    /// survives a restart even though the cache does not.
+    async fn is_nonce_used(&self, nonce: &str) -> bool {
+        if self.nonce_cache.contains(nonce) {
+            return true;
+        }
+
+        self.receipt_store
+            .is_nonce_used(nonce)
+            .await
+            .unwrap_or(false)
     }
 
-    /// Mark a nonce as used
-    fn mark_nonce_used(&self, nonce: &str) {
-        let mut cache = self.nonce_cache.lock().unwrap();
-        cache.insert(nonce.to_string(), Utc::now());
+    /// Mark a nonce as used until `expires_at`, both in the in-process cache
+    /// and durably.
+    async fn mark_nonce_used(&self, nonce: &str, expires_at: DateTime<Utc>) {
+        self.nonce_cache.insert(nonce.to_string(), expires_at);
+        self.metrics
+            .set_nonce_cache_size(self.nonce_cache.entry_count() as usize);
+
+        if let Err(e) = self.receipt_store.mark_nonce_used(nonce).await {
+            log::error!("failed to durably mark nonce {} used: {}", nonce, e);
+        }
     }
 
-    /// Log a query for audit purposes
+    /// Log a query for audit purposes and record it in the Prometheus
+    /// metrics that back `/api/v1/metrics`.
     fn log_query(
         &self,
         req: &QueryRequest,
@@ -457,6 +806,13 @@ impl RetrievalGateway {
         error: Option<&str>,
         execution_time: u64,
     ) {
+        self.metrics.observe_query(
+            &req.tenant,
+            &self.get_shard_for_tenant(&req.tenant),
+            success,
+            execution_time,
+        );
+
         let entry = QueryLogEntry {
             id: Uuid::new_v4().to_string(),
             tenant: req.tenant.clone(),
@@ -501,20 +857,35 @@ impl RetrievalGateway {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ed25519_dalek::Keypair;
+    use crate::rate_limit::{RedisFailurePolicy, TenantQuota};
+
+    fn test_rate_limits() -> RateLimiterConfig {
+        RateLimiterConfig {
+            default_quota: TenantQuota {
+                requests_per_second: 1000.0,
+                burst: 1000,
+            },
+            tenant_quotas: HashMap::new(),
+            redis_url: None,
+            failure_policy: RedisFailurePolicy::FailOpen,
+        }
+    }
 
-    #[test]
-    fn test_query_validation() {
+    #[actix_web::test]
+    async fn test_query_validation() {
         let config = GatewayConfig {
             host: "localhost".to_string(),
             port: 8080,
-            signing_key: Keypair::generate(&mut rand::thread_rng()),
             tenant_shards: HashMap::new(),
             max_query_size: 1000,
             query_timeout_ms: 5000,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
         };
-        
-        let gateway = RetrievalGateway::new(config);
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
         
         let valid_request = QueryRequest {
             tenant: "acme".to_string(),
@@ -522,6 +893,7 @@ mod tests {
             filters: None,
             limit: Some(100),
             offset: Some(0),
+            cursor: None,
         };
         
         assert!(gateway.validate_query_request(&valid_request).is_ok());
@@ -532,24 +904,28 @@ mod tests {
             filters: None,
             limit: None,
             offset: None,
+            cursor: None,
         };
         
         assert!(gateway.validate_query_request(&invalid_request).is_err());
     }
 
-    #[test]
-    fn test_query_hashing() {
+    #[actix_web::test]
+    async fn test_query_hashing() {
         let config = GatewayConfig {
             host: "localhost".to_string(),
             port: 8080,
-            signing_key: Keypair::generate(&mut rand::thread_rng()),
             tenant_shards: HashMap::new(),
             max_query_size: 1000,
             query_timeout_ms: 5000,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
         };
-        
-        let gateway = RetrievalGateway::new(config);
-        
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
         let query1 = "SELECT * FROM employees";
         let query2 = "SELECT * FROM employees";
         let query3 = "SELECT * FROM customers";
@@ -561,4 +937,335 @@ mod tests {
         assert_eq!(hash1, hash2);
         assert_ne!(hash1, hash3);
     }
+
+    #[actix_web::test]
+    async fn test_receipt_persisted_and_verifiable() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        let request = QueryRequest {
+            tenant: "acme".to_string(),
+            query: "SELECT * FROM employees".to_string(),
+            filters: None,
+            limit: None,
+            offset: None,
+            cursor: None,
+        };
+
+        let (data, _next_cursor) = gateway.execute_query(&request).await.unwrap();
+        let receipt = gateway.generate_access_receipt(&request, &data, 0);
+        gateway.receipt_store.put(&receipt).await.unwrap();
+
+        let verified = gateway
+            .verify_receipt_signature(&receipt.id)
+            .await
+            .unwrap();
+        assert_eq!(verified.id, receipt.id);
+        assert_eq!(verified.signature, receipt.signature);
+
+        assert!(gateway
+            .verify_receipt_signature("does-not-exist")
+            .await
+            .is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_receipt_verifies_after_key_rotation() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        let request = QueryRequest {
+            tenant: "acme".to_string(),
+            query: "SELECT * FROM employees".to_string(),
+            filters: None,
+            limit: None,
+            offset: None,
+            cursor: None,
+        };
+
+        let (data, _next_cursor) = gateway.execute_query(&request).await.unwrap();
+        let receipt = gateway.generate_access_receipt(&request, &data, 0);
+        gateway.receipt_store.put(&receipt).await.unwrap();
+
+        let new_kid = gateway.key_manager.rotate();
+        assert_ne!(new_kid, receipt.kid);
+
+        let verified = gateway
+            .verify_receipt_signature(&receipt.id)
+            .await
+            .unwrap();
+        assert_eq!(verified.kid, receipt.kid);
+
+        let kids: Vec<_> = gateway
+            .key_manager
+            .public_keys()
+            .iter()
+            .map(|k| k.kid.clone())
+            .collect();
+        assert!(kids.contains(&receipt.kid));
+        assert!(kids.contains(&new_kid));
+    }
+
+    #[actix_web::test]
+    async fn test_rate_limit_enforced_per_tenant() {
+        let mut config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+        config.rate_limits.default_quota = TenantQuota {
+            requests_per_second: 1.0,
+            burst: 1,
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        assert!(gateway.rate_limiter.check("acme").is_ok());
+        assert!(gateway.rate_limiter.check("acme").is_err());
+        // A different tenant has its own bucket
+        assert!(gateway.rate_limiter.check("globex").is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_query_metrics_recorded() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        let request = QueryRequest {
+            tenant: "acme".to_string(),
+            query: "SELECT * FROM employees".to_string(),
+            filters: None,
+            limit: None,
+            offset: None,
+            cursor: None,
+        };
+
+        let (data, _next_cursor) = gateway.execute_query(&request).await.unwrap();
+        let receipt = gateway.generate_access_receipt(&request, &data, 0);
+        gateway.log_query(&request, &receipt, true, None, 5);
+
+        let rendered = gateway.metrics.encode();
+        assert!(rendered.contains("gateway_queries_total"));
+        assert!(rendered.contains("tenant=\"acme\""));
+        assert!(rendered.contains("gateway_query_duration_ms"));
+    }
+
+    #[actix_web::test]
+    async fn test_cursor_pagination_resumes_and_terminates() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        let mut request = QueryRequest {
+            tenant: "acme".to_string(),
+            query: "SELECT * FROM employees".to_string(),
+            filters: None,
+            limit: Some(10),
+            offset: None,
+            cursor: None,
+        };
+
+        let mut pages = 0;
+        let mut seen = 0;
+        loop {
+            let (data, next_cursor) = gateway.execute_query(&request).await.unwrap();
+            seen += data.len();
+            pages += 1;
+            match next_cursor {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => break,
+            }
+            assert!(pages <= 10, "pagination should terminate");
+        }
+
+        assert_eq!(seen, MOCK_TOTAL_ROWS as usize);
+        assert!(pages > 1);
+    }
+
+    #[actix_web::test]
+    async fn test_cursor_rejected_for_different_tenant() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        let request = QueryRequest {
+            tenant: "acme".to_string(),
+            query: "SELECT * FROM employees".to_string(),
+            filters: None,
+            limit: Some(5),
+            offset: None,
+            cursor: None,
+        };
+
+        let (_data, next_cursor) = gateway.execute_query(&request).await.unwrap();
+        let cursor = next_cursor.expect("first page should have a next cursor");
+
+        let other_tenant_request = QueryRequest {
+            tenant: "globex".to_string(),
+            query: request.query.clone(),
+            filters: None,
+            limit: Some(5),
+            offset: None,
+            cursor: Some(cursor),
+        };
+
+        assert!(gateway.execute_query(&other_tenant_request).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_expired_nonce_treated_as_unused() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        gateway
+            .mark_nonce_used("already-expired", Utc::now() - chrono::Duration::seconds(1))
+            .await;
+        assert!(!gateway.nonce_cache.contains("already-expired"));
+
+        gateway
+            .mark_nonce_used("still-valid", Utc::now() + chrono::Duration::hours(1))
+            .await;
+        assert!(gateway.nonce_cache.contains("still-valid"));
+    }
+
+    #[actix_web::test]
+    async fn test_admin_auth_rejects_missing_or_wrong_token() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "correct-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = RetrievalGateway::new(config).await.unwrap();
+
+        let no_header = actix_web::test::TestRequest::default().to_http_request();
+        assert!(gateway.check_admin_auth(&no_header).is_err());
+
+        let wrong_header = actix_web::test::TestRequest::default()
+            .insert_header(("X-Admin-Token", "wrong-token"))
+            .to_http_request();
+        assert!(gateway.check_admin_auth(&wrong_header).is_err());
+
+        let right_header = actix_web::test::TestRequest::default()
+            .insert_header(("X-Admin-Token", "correct-token"))
+            .to_http_request();
+        assert!(gateway.check_admin_auth(&right_header).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_batch_query_rejects_oversized_batch() {
+        let config = GatewayConfig {
+            host: "localhost".to_string(),
+            port: 8080,
+            tenant_shards: HashMap::new(),
+            max_query_size: 1000,
+            query_timeout_ms: 0,
+            database_url: "sqlite::memory:".to_string(),
+            admin_token: "test-admin-token".to_string(),
+            cursor_signing_key: "test-cursor-signing-key".to_string(),
+            rate_limits: test_rate_limits(),
+        };
+
+        let gateway = web::Data::new(RetrievalGateway::new(config).await.unwrap());
+
+        let oversized_batch = BatchQueryRequest {
+            queries: (0..MAX_BATCH_SIZE + 1)
+                .map(|_| QueryRequest {
+                    tenant: "acme".to_string(),
+                    query: "SELECT * FROM employees".to_string(),
+                    filters: None,
+                    limit: None,
+                    offset: None,
+                    cursor: None,
+                })
+                .collect(),
+        };
+
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let response = RetrievalGateway::handle_batch_query(
+            req,
+            web::Json(oversized_batch),
+            gateway,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    }
 }
@@ -0,0 +1,148 @@
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{Keypair, Signature, Signer, Verifier};
+use rand::thread_rng;
+use serde::Serialize;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Maximum lifetime of a signed `AccessReceipt`. A retired signing key must
+/// stay verifiable for at least this long after rotation, since a receipt
+/// signed moments before rotation can still be presented right up until it
+/// expires.
+pub const MAX_RECEIPT_TTL: Duration = Duration::hours(24);
+
+struct KeyEntry {
+    kid: String,
+    keypair: Keypair,
+    created_at: DateTime<Utc>,
+    /// `None` while active; set to the rotation time + `MAX_RECEIPT_TTL`
+    /// once retired, after which the key is dropped from `public_keys`.
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Public, JWKS-style view of a signing key
+#[derive(Debug, Serialize, Clone)]
+pub struct PublicKeyInfo {
+    pub kid: String,
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Holds the active signing key plus any retired keys still needed to
+/// verify receipts signed before a rotation.
+pub struct KeyManager {
+    keys: Mutex<Vec<KeyEntry>>,
+}
+
+impl KeyManager {
+    /// Create a key manager with a single, freshly generated active key.
+    pub fn new() -> Self {
+        let manager = Self {
+            keys: Mutex::new(Vec::new()),
+        };
+        manager.generate_key();
+        manager
+    }
+
+    fn generate_key(&self) -> String {
+        let kid = format!("k-{}", &Uuid::new_v4().simple().to_string()[..12]);
+        let entry = KeyEntry {
+            kid: kid.clone(),
+            keypair: Keypair::generate(&mut thread_rng()),
+            created_at: Utc::now(),
+            expires_at: None,
+        };
+
+        self.keys.lock().unwrap().push(entry);
+        kid
+    }
+
+    /// Sign `data` with the current active key, returning its `kid` and the
+    /// base64-encoded signature.
+    pub fn sign(&self, data: &[u8]) -> (String, String) {
+        let keys = self.keys.lock().unwrap();
+        let active = keys
+            .iter()
+            .rev()
+            .find(|k| k.expires_at.is_none())
+            .expect("KeyManager always has an active key");
+
+        let signature = active.keypair.sign(data);
+        (
+            active.kid.clone(),
+            general_purpose::STANDARD.encode(signature.to_bytes()),
+        )
+    }
+
+    /// Verify `signature_b64` over `data` using the key identified by `kid`,
+    /// whether it is the active key or a retired-but-still-valid one.
+    pub fn verify(&self, kid: &str, data: &[u8], signature_b64: &str) -> Result<(), String> {
+        self.prune_expired();
+        let keys = self.keys.lock().unwrap();
+        let entry = keys
+            .iter()
+            .find(|k| k.kid == kid)
+            .ok_or_else(|| format!("unknown signing key id {}", kid))?;
+
+        let signature_bytes = general_purpose::STANDARD
+            .decode(signature_b64)
+            .map_err(|e| format!("invalid signature encoding: {}", e))?;
+        let signature = Signature::from_bytes(&signature_bytes)
+            .map_err(|e| format!("invalid signature: {}", e))?;
+
+        entry
+            .keypair
+            .public
+            .verify(data, &signature)
+            .map_err(|_| "Signature verification failed".to_string())
+    }
+
+    /// Generate a new active key, retiring the previous one. The retired
+    /// key remains verifiable until `MAX_RECEIPT_TTL` has elapsed, then
+    /// `prune_expired` drops it.
+    pub fn rotate(&self) -> String {
+        {
+            let mut keys = self.keys.lock().unwrap();
+            let now = Utc::now();
+            for entry in keys.iter_mut().filter(|k| k.expires_at.is_none()) {
+                entry.expires_at = Some(now + MAX_RECEIPT_TTL);
+            }
+        }
+        self.prune_expired();
+        self.generate_key()
+    }
+
+    /// Drop retired keys whose max receipt TTL has fully elapsed.
+    pub fn prune_expired(&self) {
+        let now = Utc::now();
+        self.keys
+            .lock()
+            .unwrap()
+            .retain(|k| k.expires_at.map(|exp| exp > now).unwrap_or(true));
+    }
+
+    /// All keys still relevant for verification: the active key plus any
+    /// retired keys that haven't hit their TTL yet.
+    pub fn public_keys(&self) -> Vec<PublicKeyInfo> {
+        self.prune_expired();
+        self.keys
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|k| PublicKeyInfo {
+                kid: k.kid.clone(),
+                public_key: general_purpose::STANDARD.encode(k.keypair.public.as_bytes()),
+                created_at: k.created_at,
+                expires_at: k.expires_at,
+            })
+            .collect()
+    }
+}
+
+impl Default for KeyManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
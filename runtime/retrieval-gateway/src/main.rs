@@ -1,12 +1,17 @@
 use actix_web::App;
 use std::collections::HashMap;
 use std::env;
-use ed25519_dalek::Keypair;
-use rand::thread_rng;
 
+mod cursor;
 mod gateway;
+mod keys;
+mod metrics;
+mod nonce_cache;
+mod rate_limit;
+mod receipt_store;
 
 use gateway::{RetrievalGateway, GatewayConfig};
+use rate_limit::{RateLimiterConfig, RedisFailurePolicy, TenantQuota};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -29,31 +34,60 @@ async fn main() -> std::io::Result<()> {
         .unwrap_or_else(|_| "5000".to_string())
         .parse::<u64>()
         .expect("Invalid query timeout");
-    
-    // Generate signing key (in production, load from secure storage)
-    let signing_key = Keypair::generate(&mut thread_rng());
-    
+
+    let database_url = env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "sqlite://retrieval_gateway.db".to_string());
+
+    let admin_token = env::var("ADMIN_TOKEN")
+        .expect("ADMIN_TOKEN must be set to authorize admin endpoints like /keys/rotate");
+
+    let cursor_signing_key = env::var("CURSOR_SIGNING_KEY")
+        .expect("CURSOR_SIGNING_KEY must be set to HMAC-tag pagination cursors");
+
     // Configure tenant shards
     let mut tenant_shards = HashMap::new();
     tenant_shards.insert("acme".to_string(), "acme-shard-1".to_string());
     tenant_shards.insert("globex".to_string(), "globex-shard-1".to_string());
-    
+
+    let rate_limits = RateLimiterConfig {
+        default_quota: TenantQuota {
+            requests_per_second: env::var("RATE_LIMIT_DEFAULT_RPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50.0),
+            burst: env::var("RATE_LIMIT_DEFAULT_BURST")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+        },
+        tenant_quotas: HashMap::new(),
+        redis_url: env::var("REDIS_URL").ok(),
+        failure_policy: match env::var("RATE_LIMIT_FAIL_POLICY").as_deref() {
+            Ok("closed") => RedisFailurePolicy::FailClosed,
+            _ => RedisFailurePolicy::FailOpen,
+        },
+    };
+
     // Create gateway configuration
     let config = GatewayConfig {
         host,
         port,
-        signing_key,
         tenant_shards,
         max_query_size,
         query_timeout_ms,
+        database_url,
+        rate_limits,
+        admin_token,
+        cursor_signing_key,
     };
-    
+
     log::info!("Starting Retrieval Gateway on {}:{}", config.host, config.port);
     log::info!("Max query size: {} bytes", config.max_query_size);
     log::info!("Query timeout: {} ms", config.query_timeout_ms);
     log::info!("Tenant shards: {:?}", config.tenant_shards);
-    
+    log::info!("Database URL: {}", config.database_url);
+
     // Create and start the gateway
-    let gateway = RetrievalGateway::new(config);
+    let gateway = RetrievalGateway::new(config).await?;
     gateway.start().await
 }
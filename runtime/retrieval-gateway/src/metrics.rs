@@ -0,0 +1,126 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+/// Prometheus metrics for the gateway, scraped via `GET /api/v1/metrics`.
+///
+/// All updates are plain atomic counter/histogram observations, so recording
+/// them on the request path (in `handle_query`, `log_query`, and
+/// `verify_receipt`) is O(1) regardless of query volume, unlike
+/// `get_statistics`, which scans the full in-memory query log on every call.
+pub struct Metrics {
+    registry: Registry,
+    query_duration_ms: HistogramVec,
+    queries_total: IntCounterVec,
+    receipt_verifications_total: IntCounterVec,
+    nonce_cache_size: IntGauge,
+}
+
+impl Metrics {
+    /// Create a fresh, independent metrics registry with all metric families
+    /// registered only into it. Building the families directly (rather than
+    /// via the `register_*!` macros, which register into prometheus's
+    /// process-global default registry) keeps each `Metrics` instance
+    /// isolated, so constructing more than one per process -- as every test
+    /// and every `RetrievalGateway::new` does -- doesn't panic on a
+    /// duplicate collector registration.
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let query_duration_ms = HistogramVec::new(
+            HistogramOpts::new(
+                "gateway_query_duration_ms",
+                "Query execution time in milliseconds",
+            )
+            .buckets(vec![
+                1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 5000.0,
+            ]),
+            &["tenant", "shard"],
+        )
+        .expect("gateway_query_duration_ms metric construction");
+
+        let queries_total = IntCounterVec::new(
+            Opts::new(
+                "gateway_queries_total",
+                "Total number of queries handled, labeled by tenant and outcome",
+            ),
+            &["tenant", "outcome"],
+        )
+        .expect("gateway_queries_total metric construction");
+
+        let receipt_verifications_total = IntCounterVec::new(
+            Opts::new(
+                "gateway_receipt_verifications_total",
+                "Total number of receipt verification attempts, labeled by outcome",
+            ),
+            &["outcome"],
+        )
+        .expect("gateway_receipt_verifications_total metric construction");
+
+        let nonce_cache_size = IntGauge::new(
+            "gateway_nonce_cache_size",
+            "Current number of entries in the in-process nonce replay cache",
+        )
+        .expect("gateway_nonce_cache_size metric construction");
+
+        registry
+            .register(Box::new(query_duration_ms.clone()))
+            .expect("register gateway_query_duration_ms");
+        registry
+            .register(Box::new(queries_total.clone()))
+            .expect("register gateway_queries_total");
+        registry
+            .register(Box::new(receipt_verifications_total.clone()))
+            .expect("register gateway_receipt_verifications_total");
+        registry
+            .register(Box::new(nonce_cache_size.clone()))
+            .expect("register gateway_nonce_cache_size");
+
+        Self {
+            registry,
+            query_duration_ms,
+            queries_total,
+            receipt_verifications_total,
+            nonce_cache_size,
+        }
+    }
+
+    /// Record the outcome and latency of a completed query.
+    pub fn observe_query(&self, tenant: &str, shard: &str, success: bool, duration_ms: u64) {
+        self.query_duration_ms
+            .with_label_values(&[tenant, shard])
+            .observe(duration_ms as f64);
+
+        let outcome = if success { "success" } else { "failure" };
+        self.queries_total
+            .with_label_values(&[tenant, outcome])
+            .inc();
+    }
+
+    /// Record the outcome of a receipt verification attempt.
+    pub fn record_receipt_verification(&self, outcome: &str) {
+        self.receipt_verifications_total
+            .with_label_values(&[outcome])
+            .inc();
+    }
+
+    /// Update the nonce cache size gauge to the current cache length.
+    pub fn set_nonce_cache_size(&self, size: usize) {
+        self.nonce_cache_size.set(size as i64);
+    }
+
+    /// Render all registered metrics in Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("encode metrics");
+        String::from_utf8(buffer).expect("metrics encoding is valid utf-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
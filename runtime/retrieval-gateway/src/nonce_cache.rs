@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use moka::Expiry;
+use std::time::{Duration as StdDuration, Instant};
+
+/// Upper bound on distinct nonces tracked at once. Beyond this, the cache's
+/// LRU eviction policy reclaims the least-recently-used entries, so a
+/// flood of unique nonces can't exhaust memory even before their TTL elapses.
+const MAX_TRACKED_NONCES: u64 = 1_000_000;
+
+/// Expires each nonce at the `expires_at` it was inserted with, rather than
+/// a fixed TTL shared by every entry -- receipts don't all carry the same
+/// expiry (error receipts are shorter-lived than successful ones).
+struct NonceExpiry;
+
+impl Expiry<String, DateTime<Utc>> for NonceExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        expires_at: &DateTime<Utc>,
+        _created_at: Instant,
+    ) -> Option<StdDuration> {
+        Some((*expires_at - Utc::now()).to_std().unwrap_or(StdDuration::ZERO))
+    }
+}
+
+/// Concurrent, TTL-evicting cache of consumed receipt nonces, replacing a
+/// single `std::sync::Mutex<HashMap<..>>` that grew without bound and would
+/// have serialized every request through one lock. Reads and writes go
+/// straight to moka's internally sharded map rather than a global mutex.
+pub struct NonceCache {
+    cache: Cache<String, DateTime<Utc>>,
+}
+
+impl NonceCache {
+    pub fn new() -> Self {
+        let cache = Cache::builder()
+            .max_capacity(MAX_TRACKED_NONCES)
+            .eviction_policy(moka::policy::EvictionPolicy::lru())
+            .expire_after(NonceExpiry)
+            .build();
+        Self { cache }
+    }
+
+    /// Whether `nonce` is currently tracked as used. An entry that has
+    /// passed its `expires_at` but hasn't been evicted yet is treated as
+    /// unused, matching the semantics of a receipt that can no longer be
+    /// replayed anyway because it has expired.
+    pub fn contains(&self, nonce: &str) -> bool {
+        self.cache
+            .get(nonce)
+            .map(|expires_at| expires_at > Utc::now())
+            .unwrap_or(false)
+    }
+
+    /// Record `nonce` as used until `expires_at`.
+    pub fn insert(&self, nonce: String, expires_at: DateTime<Utc>) {
+        self.cache.insert(nonce, expires_at);
+    }
+
+    /// Approximate number of entries currently held, for the nonce cache
+    /// size gauge.
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    /// Drop expired entries. moka otherwise only evicts lazily on access, so
+    /// a nonce that's never looked up again would linger in memory until
+    /// then; call this periodically from a background task.
+    pub fn run_pending_tasks(&self) {
+        self.cache.run_pending_tasks();
+    }
+}
+
+impl Default for NonceCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Requests-per-second and burst allowance for a single tenant.
+#[derive(Debug, Clone, Copy)]
+pub struct TenantQuota {
+    pub requests_per_second: f64,
+    pub burst: u32,
+}
+
+/// What to do when the shared Redis counter can't be reached: keep
+/// enforcing the local-only quota (`FailOpen`), or reject all requests
+/// until Redis is reachable again (`FailClosed`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedisFailurePolicy {
+    FailOpen,
+    FailClosed,
+}
+
+/// Per-tenant quotas plus how the limiter should be wired up.
+#[derive(Debug, Clone)]
+pub struct RateLimiterConfig {
+    pub default_quota: TenantQuota,
+    pub tenant_quotas: HashMap<String, TenantQuota>,
+    pub redis_url: Option<String>,
+    pub failure_policy: RedisFailurePolicy,
+}
+
+/// A request was rejected; retry after this many seconds.
+#[derive(Debug)]
+pub struct RateLimitExceeded {
+    pub retry_after_secs: u64,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: DateTime<Utc>,
+    /// Tokens taken locally since the last reconciliation tick; drained and
+    /// shipped to Redis by `reconcile` so the shared counter reflects actual
+    /// request volume rather than one tick per instance.
+    consumed_since_reconcile: u64,
+}
+
+/// Two-layer per-tenant rate limiter: a local token bucket handles every
+/// request on the hot path, periodically reconciled against a shared Redis
+/// counter so quotas hold across horizontally scaled gateway instances.
+pub struct RateLimiter {
+    config: RateLimiterConfig,
+    local_buckets: Mutex<HashMap<String, TokenBucket>>,
+    redis: Option<redis::Client>,
+    /// Whether `config.redis_url` was set, independent of whether building
+    /// the `redis::Client` for it actually succeeded. `check` gates
+    /// fail-closed on this rather than on `redis.is_some()`, so a malformed
+    /// `redis_url` can't silently fail open under `FailClosed` just because
+    /// `redis::Client::open` failed at construction.
+    redis_configured: bool,
+    /// Set when Redis can't be reached -- either because reconciliation
+    /// lost its connection, or because the client couldn't even be built at
+    /// construction time. Consulted by `check` only under
+    /// `RedisFailurePolicy::FailClosed`.
+    degraded: AtomicBool,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimiterConfig) -> Self {
+        let redis_configured = config.redis_url.is_some();
+        let redis = config.redis_url.as_ref().and_then(|url| {
+            redis::Client::open(url.as_str())
+                .map_err(|e| log::warn!("failed to configure Redis client at {}: {}", url, e))
+                .ok()
+        });
+        // A client that failed to build can never be reconciled (reconcile
+        // skips entirely when `redis` is `None`), so treat it as degraded
+        // from the start rather than leaving `FailClosed` never engaged.
+        let degraded = redis_configured && redis.is_none();
+
+        Self {
+            config,
+            local_buckets: Mutex::new(HashMap::new()),
+            redis,
+            redis_configured,
+            degraded: AtomicBool::new(degraded),
+        }
+    }
+
+    fn quota_for(&self, tenant: &str) -> TenantQuota {
+        self.config
+            .tenant_quotas
+            .get(tenant)
+            .copied()
+            .unwrap_or(self.config.default_quota)
+    }
+
+    /// Admit or reject a single request from `tenant`.
+    pub fn check(&self, tenant: &str) -> Result<(), RateLimitExceeded> {
+        if self.redis_configured
+            && self.config.failure_policy == RedisFailurePolicy::FailClosed
+            && self.degraded.load(Ordering::Relaxed)
+        {
+            return Err(RateLimitExceeded {
+                retry_after_secs: 1,
+            });
+        }
+
+        let quota = self.quota_for(tenant);
+        if self.take_local_token(tenant, &quota) {
+            Ok(())
+        } else {
+            Err(RateLimitExceeded {
+                retry_after_secs: (1.0 / quota.requests_per_second.max(0.001)).ceil() as u64,
+            })
+        }
+    }
+
+    fn take_local_token(&self, tenant: &str, quota: &TenantQuota) -> bool {
+        let mut buckets = self.local_buckets.lock().unwrap();
+        let bucket = buckets.entry(tenant.to_string()).or_insert_with(|| TokenBucket {
+            tokens: quota.burst as f64,
+            last_refill: Utc::now(),
+            consumed_since_reconcile: 0,
+        });
+
+        let now = Utc::now();
+        let elapsed_secs = (now - bucket.last_refill).num_milliseconds() as f64 / 1000.0;
+        bucket.tokens = (bucket.tokens + elapsed_secs * quota.requests_per_second).min(quota.burst as f64);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.consumed_since_reconcile += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Run forever, periodically syncing each tenant's local bucket against
+    /// a shared Redis counter. Intended to be spawned as a background task.
+    pub async fn run_reconciliation_loop(&self) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            self.reconcile().await;
+        }
+    }
+
+    async fn reconcile(&self) {
+        let Some(client) = &self.redis else {
+            return;
+        };
+
+        let mut conn = match client.get_multiplexed_async_connection().await {
+            Ok(conn) => {
+                // A live connection is itself the signal that Redis is
+                // reachable again; clear `degraded` here rather than only on
+                // a per-tenant `INCR` success, which never runs (and so
+                // never recovers `degraded`) for a tenant with no traffic
+                // since the last tick.
+                self.degraded.store(false, Ordering::Relaxed);
+                conn
+            }
+            Err(e) => {
+                log::warn!("rate limiter lost connection to Redis: {}", e);
+                self.degraded.store(true, Ordering::Relaxed);
+                return;
+            }
+        };
+
+        let tenants: Vec<String> = {
+            let buckets = self.local_buckets.lock().unwrap();
+            buckets.keys().cloned().collect()
+        };
+
+        for tenant in tenants {
+            // Drain the tokens this instance has actually handed out since
+            // the last tick, so the shared counter tracks real request
+            // volume instead of incrementing by a flat 1 every tick
+            // regardless of traffic.
+            let consumed = {
+                let mut buckets = self.local_buckets.lock().unwrap();
+                match buckets.get_mut(&tenant) {
+                    Some(bucket) => std::mem::take(&mut bucket.consumed_since_reconcile),
+                    None => 0,
+                }
+            };
+
+            if consumed == 0 {
+                continue;
+            }
+
+            let quota = self.quota_for(&tenant);
+            let window = Utc::now().timestamp();
+            let key = format!("ratelimit:{}:{}", tenant, window);
+
+            let count: Result<i64, _> = async {
+                let count: i64 = conn.incr(&key, consumed as i64).await?;
+                let _: () = conn.expire(&key, 2).await?;
+                Ok(count)
+            }
+            .await;
+
+            match count {
+                Ok(count) => {
+                    // A tenant running hot across other instances exhausts
+                    // its global quota even if this instance's local bucket
+                    // still has tokens left; zero it out so requests are
+                    // rejected locally until the bucket refills.
+                    if count as f64 > quota.requests_per_second {
+                        let mut buckets = self.local_buckets.lock().unwrap();
+                        if let Some(bucket) = buckets.get_mut(&tenant) {
+                            bucket.tokens = 0.0;
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::warn!("rate limiter reconciliation failed for tenant {}: {}", tenant, e);
+                    self.degraded.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::any::{AnyPoolOptions, AnyRow};
+use sqlx::{AnyPool, Row};
+
+use crate::gateway::AccessReceipt;
+
+/// Whether `database_url` points at a SQLite in-memory database, where each
+/// pooled connection would otherwise get its own private, empty database.
+fn is_in_memory_sqlite(database_url: &str) -> bool {
+    database_url.starts_with("sqlite::memory:") || database_url.starts_with("sqlite://:memory:")
+}
+
+/// Pluggable persistence for `AccessReceipt`s.
+///
+/// The gateway only ever needs to put a receipt once (on successful query
+/// execution), look one up by id (on `/receipt/{id}/verify`), and record
+/// and check whether a receipt's nonce has been used, so the trait stays
+/// deliberately small.
+#[async_trait]
+pub trait ReceiptStore: Send + Sync {
+    async fn put(&self, receipt: &AccessReceipt) -> Result<(), String>;
+    async fn get(&self, id: &str) -> Result<Option<AccessReceipt>, String>;
+
+    /// Record that `nonce` has been presented for replay verification.
+    async fn mark_nonce_used(&self, nonce: &str) -> Result<(), String>;
+
+    /// Whether `nonce` has already been presented for replay verification.
+    async fn is_nonce_used(&self, nonce: &str) -> Result<bool, String>;
+}
+
+/// `ReceiptStore` backed by `sqlx::Any`, so the same queries run against
+/// either SQLite or Postgres depending on the scheme of `DATABASE_URL`.
+pub struct SqlxReceiptStore {
+    pool: AnyPool,
+}
+
+impl SqlxReceiptStore {
+    /// Connect to `database_url` and ensure the `receipts` table exists.
+    pub async fn connect(database_url: &str) -> Result<Self, String> {
+        sqlx::any::install_default_drivers();
+
+        // Each pooled connection to `sqlite::memory:` is its own private,
+        // independent database, so a `CREATE TABLE` run on one connection
+        // (below) would be invisible to queries served by any other
+        // connection in the pool. Cap the pool at a single connection for
+        // the in-memory case so every query in a process shares the
+        // database the schema was created in; real (file- or
+        // network-backed) databases keep the normal pool size.
+        let max_connections = if is_in_memory_sqlite(database_url) { 1 } else { 10 };
+
+        let pool = AnyPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(database_url)
+            .await
+            .map_err(|e| format!("failed to connect to {}: {}", database_url, e))?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS receipts (
+                id TEXT PRIMARY KEY,
+                tenant TEXT NOT NULL,
+                subject TEXT NOT NULL,
+                shard TEXT NOT NULL,
+                query_hash TEXT NOT NULL,
+                result_hash TEXT NOT NULL,
+                nonce TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                signature TEXT NOT NULL,
+                kid TEXT NOT NULL,
+                nonce_used_at TEXT
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("failed to create receipts table: {}", e))?;
+
+        Ok(Self { pool })
+    }
+
+    fn row_to_receipt(row: AnyRow) -> Result<AccessReceipt, String> {
+        let expires_at: String = row.try_get("expires_at").map_err(|e| e.to_string())?;
+
+        Ok(AccessReceipt {
+            id: row.try_get("id").map_err(|e| e.to_string())?,
+            tenant: row.try_get("tenant").map_err(|e| e.to_string())?,
+            subject: row.try_get("subject").map_err(|e| e.to_string())?,
+            shard: row.try_get("shard").map_err(|e| e.to_string())?,
+            query_hash: row.try_get("query_hash").map_err(|e| e.to_string())?,
+            result_hash: row.try_get("result_hash").map_err(|e| e.to_string())?,
+            nonce: row.try_get("nonce").map_err(|e| e.to_string())?,
+            expires_at: expires_at
+                .parse()
+                .map_err(|e| format!("invalid expires_at in receipts table: {}", e))?,
+            signature: row.try_get("signature").map_err(|e| e.to_string())?,
+            kid: row.try_get("kid").map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+#[async_trait]
+impl ReceiptStore for SqlxReceiptStore {
+    async fn put(&self, receipt: &AccessReceipt) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO receipts
+                (id, tenant, subject, shard, query_hash, result_hash, nonce, expires_at, signature, kid)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(&receipt.id)
+        .bind(&receipt.tenant)
+        .bind(&receipt.subject)
+        .bind(&receipt.shard)
+        .bind(&receipt.query_hash)
+        .bind(&receipt.result_hash)
+        .bind(&receipt.nonce)
+        .bind(receipt.expires_at.to_rfc3339())
+        .bind(&receipt.signature)
+        .bind(&receipt.kid)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("failed to persist receipt {}: {}", receipt.id, e))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<AccessReceipt>, String> {
+        let row = sqlx::query("SELECT * FROM receipts WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("failed to load receipt {}: {}", id, e))?;
+
+        row.map(Self::row_to_receipt).transpose()
+    }
+
+    async fn mark_nonce_used(&self, nonce: &str) -> Result<(), String> {
+        sqlx::query("UPDATE receipts SET nonce_used_at = $1 WHERE nonce = $2")
+            .bind(Utc::now().to_rfc3339())
+            .bind(nonce)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("failed to mark nonce {} used: {}", nonce, e))?;
+
+        Ok(())
+    }
+
+    async fn is_nonce_used(&self, nonce: &str) -> Result<bool, String> {
+        let row = sqlx::query("SELECT nonce_used_at FROM receipts WHERE nonce = $1")
+            .bind(nonce)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("failed to look up nonce {}: {}", nonce, e))?;
+
+        match row {
+            Some(row) => {
+                let used_at: Option<String> =
+                    row.try_get("nonce_used_at").map_err(|e| e.to_string())?;
+                Ok(used_at.is_some())
+            }
+            None => Ok(false),
+        }
+    }
+}